@@ -1,9 +1,13 @@
+use std::collections::HashMap;
 use std::env;
 use std::io::{self, Read};
 use std::path::Path;
 use std::process;
 use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
+use tiktoken_rs::tokenizer::{Tokenizer, get_tokenizer};
 use tiktoken_rs::{CoreBPE, cl100k_base, o200k_base, p50k_base, p50k_edit, r50k_base};
 
 const DEFAULT_ENCODING: &str = "o200k_base";
@@ -20,6 +24,7 @@ enum Mode {
     Count,
     Diff,
     GitDiff(Vec<String>),
+    Truncate { n: usize, from_tail: bool },
 }
 
 fn run() -> Result<(), String> {
@@ -29,6 +34,9 @@ fn run() -> Result<(), String> {
     let mut encoding = DEFAULT_ENCODING.to_string();
     let mut mode = Mode::Count;
     let mut net_output = false;
+    let mut json_output = false;
+    let mut by_file = false;
+    let mut paths: Vec<String> = Vec::new();
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
@@ -38,6 +46,12 @@ fn run() -> Result<(), String> {
                 };
                 encoding = value;
             }
+            "--model" => {
+                let Some(value) = args.next() else {
+                    return Err("missing value for --model".into());
+                };
+                encoding = value;
+            }
             "-d" | "--diff" => {
                 mode = Mode::Diff;
             }
@@ -49,6 +63,20 @@ fn run() -> Result<(), String> {
             "--net" => {
                 net_output = true;
             }
+            "--json" => {
+                json_output = true;
+            }
+            "--by-file" => {
+                by_file = true;
+            }
+            "--truncate" | "--head" => {
+                let n = parse_count(&mut args, &arg)?;
+                mode = Mode::Truncate { n, from_tail: false };
+            }
+            "--tail" => {
+                let n = parse_count(&mut args, &arg)?;
+                mode = Mode::Truncate { n, from_tail: true };
+            }
             "-h" | "--help" => {
                 print_help(&program);
                 return Ok(());
@@ -57,59 +85,132 @@ fn run() -> Result<(), String> {
                 print_supported();
                 return Ok(());
             }
-            other => {
+            other if other.starts_with('-') => {
                 return Err(format!("unrecognized argument '{other}'"));
             }
+            other => {
+                paths.push(other.to_string());
+            }
         }
     }
 
-    let tokenizer = load_encoding(&encoding)?;
+    let (tokenizer, encoding_name) = load_encoding(&encoding)?;
 
     match mode {
         Mode::Count => {
             if net_output {
                 return Err("--net can only be used with --diff or --git".into());
             }
-            let text = read_stdin()?;
-            let tokens = tokenizer.encode_with_special_tokens(&text);
-            println!("{}", tokens.len());
+            if by_file {
+                return Err("--by-file can only be used with --diff or --git".into());
+            }
+            if paths.is_empty() {
+                let text = read_stdin()?;
+                let tokens = tokenizer.encode_with_special_tokens(&text);
+                print_count_result(tokens.len(), &encoding_name, json_output);
+            } else {
+                let results = count_files(tokenizer, paths)?;
+                print_file_count_results(&results, &encoding_name, json_output);
+            }
         }
         Mode::Diff => {
             let text = read_stdin()?;
-            let (added, removed) = diff_token_totals(&tokenizer, &text);
-            print_diff_totals(added, removed, net_output);
+            if by_file {
+                let results = diff_token_totals_by_file(&tokenizer, &text);
+                print_diff_totals_by_file(&results, net_output, &encoding_name, json_output);
+            } else {
+                let (added, removed) = diff_token_totals(&tokenizer, &text);
+                print_diff_totals(added, removed, net_output, &encoding_name, json_output);
+            }
         }
         Mode::GitDiff(diff_args) => {
             let diff_text = run_git_diff(&diff_args)?;
-            let (added, removed) = diff_token_totals(&tokenizer, &diff_text);
-            print_diff_totals(added, removed, net_output);
+            if by_file {
+                let results = diff_token_totals_by_file(&tokenizer, &diff_text);
+                print_diff_totals_by_file(&results, net_output, &encoding_name, json_output);
+            } else {
+                let (added, removed) = diff_token_totals(&tokenizer, &diff_text);
+                print_diff_totals(added, removed, net_output, &encoding_name, json_output);
+            }
+        }
+        Mode::Truncate { n, from_tail } => {
+            if net_output {
+                return Err("--net can only be used with --diff or --git".into());
+            }
+            if by_file {
+                return Err("--by-file can only be used with --diff or --git".into());
+            }
+            let text = read_stdin()?;
+            let tokens = tokenizer.encode_with_special_tokens(&text);
+            let window = truncate_tokens(&tokens, n, from_tail);
+            print!("{}", decode_lossy(&tokenizer, window));
         }
     }
 
     Ok(())
 }
 
-fn load_encoding(name: &str) -> Result<CoreBPE, String> {
+fn parse_count(args: &mut env::Args, flag: &str) -> Result<usize, String> {
+    let value = args
+        .next()
+        .ok_or_else(|| format!("missing value for {flag}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid token count '{value}' for {flag}"))
+}
+
+// Example model names shown by --list; actual resolution below covers the
+// full set tiktoken knows about, including versioned aliases.
+const MODEL_EXAMPLES: &[&str] = &[
+    "gpt-4o",
+    "gpt-4",
+    "gpt-3.5-turbo",
+    "text-embedding-3-small",
+];
+
+fn tokenizer_encoding_name(tokenizer: Tokenizer) -> &'static str {
+    match tokenizer {
+        Tokenizer::O200kBase => "o200k_base",
+        Tokenizer::Cl100kBase => "cl100k_base",
+        Tokenizer::P50kBase => "p50k_base",
+        Tokenizer::P50kEdit => "p50k_edit",
+        Tokenizer::R50kBase => "r50k_base",
+        Tokenizer::Gpt2 => "gpt2",
+    }
+}
+
+fn load_encoding(name: &str) -> Result<(CoreBPE, String), String> {
     match name {
-        "o200k_base" => o200k_base().map_err(|err| err.to_string()),
-        "cl100k_base" => cl100k_base().map_err(|err| err.to_string()),
-        "p50k_base" => p50k_base().map_err(|err| err.to_string()),
-        "p50k_edit" => p50k_edit().map_err(|err| err.to_string()),
-        "r50k_base" | "gpt2" => r50k_base().map_err(|err| err.to_string()),
-        other => Err(format!("unsupported encoding '{other}'")),
+        "o200k_base" | "cl100k_base" | "p50k_base" | "p50k_edit" | "r50k_base" | "gpt2" => {
+            let canonical = if name == "gpt2" { "r50k_base" } else { name };
+            let bpe = match canonical {
+                "o200k_base" => o200k_base(),
+                "cl100k_base" => cl100k_base(),
+                "p50k_base" => p50k_base(),
+                "p50k_edit" => p50k_edit(),
+                "r50k_base" => r50k_base(),
+                _ => unreachable!(),
+            }
+            .map_err(|err| err.to_string())?;
+            Ok((bpe, canonical.to_string()))
+        }
+        other => match get_tokenizer(other) {
+            Some(tokenizer) => load_encoding(tokenizer_encoding_name(tokenizer)),
+            None => Err(format!("unsupported encoding or model '{other}'")),
+        },
     }
 }
 
 fn print_help(program: &str) {
     println!("{program} — fast token counter using tiktoken-rs");
     println!();
-    println!("Usage: {program} [OPTIONS] < input");
+    println!("Usage: {program} [OPTIONS] [FILE...] < input");
     println!();
     println!("Options:");
     let options = [
         (
             "-e, --encoding <name>",
-            format!("Select tokenizer (default: {DEFAULT_ENCODING})"),
+            format!("Select tokenizer by encoding or model name (default: {DEFAULT_ENCODING})"),
         ),
         (
             "-d, --diff",
@@ -123,12 +224,36 @@ fn print_help(program: &str) {
             "--net",
             "With --diff/--git, print net token delta instead of added/removed totals".to_string(),
         ),
+        (
+            "--model <name>",
+            "Select tokenizer by model name (e.g. gpt-4o, gpt-3.5-turbo)".to_string(),
+        ),
+        (
+            "--json",
+            "Emit a JSON object instead of plain counts".to_string(),
+        ),
+        (
+            "--by-file",
+            "With --diff/--git, print a per-file added/removed breakdown".to_string(),
+        ),
+        (
+            "--truncate, --head <N>",
+            "Clip stdin to the first N tokens and print the result".to_string(),
+        ),
+        (
+            "--tail <N>",
+            "Clip stdin to the last N tokens and print the result".to_string(),
+        ),
         ("--list", "Show supported tokenizer names".to_string()),
         ("-h, --help", "Show this message".to_string()),
     ];
     for (flag, desc) in options {
         println!("  {:<22} {}", flag, desc);
     }
+    println!();
+    println!(
+        "When counting, one or more FILE arguments are counted in parallel with a per-file\nbreakdown plus a grand total, instead of reading from stdin. The --diff, --truncate,\n--head and --tail modes always read stdin, and they and --git ignore FILE arguments."
+    );
 }
 
 fn print_supported() {
@@ -139,6 +264,13 @@ fn print_supported() {
     println!("  p50k_edit");
     println!("  r50k_base");
     println!("  gpt2");
+    println!();
+    println!("Model name examples (see --model):");
+    for model in MODEL_EXAMPLES {
+        if let Some(tokenizer) = get_tokenizer(model) {
+            println!("  {:<28} {}", model, tokenizer_encoding_name(tokenizer));
+        }
+    }
 }
 
 fn display_name(raw: &str) -> String {
@@ -167,15 +299,221 @@ fn diff_token_totals(tokenizer: &CoreBPE, diff: &str) -> (usize, usize) {
     (added, removed)
 }
 
-fn print_diff_totals(added: usize, removed: usize, net_output: bool) {
+fn diff_token_totals_by_file(tokenizer: &CoreBPE, diff: &str) -> Vec<(String, usize, usize)> {
+    let mut order: Vec<String> = Vec::new();
+    let mut totals: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut pending_old: Option<String> = None;
+
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("--- ") {
+            pending_old = parse_diff_header_path(rest);
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("+++ ") {
+            let new_path = parse_diff_header_path(rest);
+            current = new_path.or_else(|| pending_old.take());
+            if let Some(path) = &current {
+                totals.entry(path.clone()).or_insert_with(|| {
+                    order.push(path.clone());
+                    (0, 0)
+                });
+            }
+            continue;
+        }
+
+        let Some(path) = current.as_ref() else {
+            continue;
+        };
+        if let Some(rest) = line.strip_prefix('+') {
+            totals.get_mut(path).unwrap().0 += tokenizer.encode_with_special_tokens(rest).len();
+        } else if let Some(rest) = line.strip_prefix('-') {
+            totals.get_mut(path).unwrap().1 += tokenizer.encode_with_special_tokens(rest).len();
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|path| {
+            let (added, removed) = totals[&path];
+            (path, added, removed)
+        })
+        .collect()
+}
+
+fn parse_diff_header_path(rest: &str) -> Option<String> {
+    let trimmed = rest.trim();
+    if trimmed == "/dev/null" {
+        return None;
+    }
+    let path = trimmed
+        .strip_prefix("a/")
+        .or_else(|| trimmed.strip_prefix("b/"))
+        .unwrap_or(trimmed);
+    Some(path.to_string())
+}
+
+fn print_diff_totals_by_file(
+    results: &[(String, usize, usize)],
+    net_output: bool,
+    encoding: &str,
+    json_output: bool,
+) {
+    let mut total_added = 0usize;
+    let mut total_removed = 0usize;
+
+    if json_output {
+        let files: Vec<String> = results
+            .iter()
+            .map(|(path, added, removed)| {
+                total_added += added;
+                total_removed += removed;
+                let net = (*added as i128) - (*removed as i128);
+                format!(
+                    "{{\"path\": \"{}\", \"added\": {added}, \"removed\": {removed}, \"net\": {net}}}",
+                    json_escape(path)
+                )
+            })
+            .collect();
+        let total_net = (total_added as i128) - (total_removed as i128);
+        println!(
+            "{{\"files\": [{}], \"added\": {total_added}, \"removed\": {total_removed}, \"net\": {total_net}, \"encoding\": \"{}\"}}",
+            files.join(", "),
+            json_escape(encoding)
+        );
+        return;
+    }
+
+    for (path, added, removed) in results {
+        total_added += added;
+        total_removed += removed;
+        if net_output {
+            let net = (*added as i128) - (*removed as i128);
+            println!("{net}\t{path}");
+        } else {
+            println!("{added} {removed}\t{path}");
+        }
+    }
+
     if net_output {
-        let net_total = (added as i128) - (removed as i128);
+        let total_net = (total_added as i128) - (total_removed as i128);
+        println!("{total_net}\ttotal");
+    } else {
+        println!("{total_added} {total_removed}\ttotal");
+    }
+}
+
+fn count_files(tokenizer: CoreBPE, paths: Vec<String>) -> Result<Vec<(String, usize)>, String> {
+    let tokenizer = Arc::new(tokenizer);
+    let worker_count = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(paths.len().max(1));
+    let queue = Mutex::new(paths.into_iter().enumerate().collect::<Vec<_>>());
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            let queue = &queue;
+            let tokenizer = Arc::clone(&tokenizer);
+            let results = &results;
+            scope.spawn(move || loop {
+                let Some((index, path)) = queue.lock().unwrap().pop() else {
+                    break;
+                };
+                let outcome = count_file(&tokenizer, &path);
+                results.lock().unwrap().push((index, path, outcome));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _, _)| *index);
+    results
+        .into_iter()
+        .map(|(_, path, outcome)| outcome.map(|count| (path, count)))
+        .collect()
+}
+
+fn count_file(tokenizer: &CoreBPE, path: &str) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    Ok(tokenizer.encode_with_special_tokens(&text).len())
+}
+
+fn truncate_tokens(tokens: &[usize], n: usize, from_tail: bool) -> &[usize] {
+    if from_tail {
+        let start = tokens.len().saturating_sub(n);
+        &tokens[start..]
+    } else {
+        &tokens[..tokens.len().min(n)]
+    }
+}
+
+fn decode_lossy(tokenizer: &CoreBPE, tokens: &[usize]) -> String {
+    let bytes = tokenizer._decode_native(tokens);
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn print_diff_totals(added: usize, removed: usize, net_output: bool, encoding: &str, json_output: bool) {
+    let net_total = (added as i128) - (removed as i128);
+    if json_output {
+        println!(
+            "{{\"added\": {added}, \"removed\": {removed}, \"net\": {net_total}, \"encoding\": \"{}\"}}",
+            json_escape(encoding)
+        );
+    } else if net_output {
         println!("{net_total}");
     } else {
         println!("{} {}", added, removed);
     }
 }
 
+fn print_count_result(tokens: usize, encoding: &str, json_output: bool) {
+    if json_output {
+        println!(
+            "{{\"tokens\": {tokens}, \"encoding\": \"{}\"}}",
+            json_escape(encoding)
+        );
+    } else {
+        println!("{tokens}");
+    }
+}
+
+fn print_file_count_results(results: &[(String, usize)], encoding: &str, json_output: bool) {
+    let total: usize = results.iter().map(|(_, count)| count).sum();
+    if json_output {
+        let files: Vec<String> = results
+            .iter()
+            .map(|(path, count)| format!("{{\"path\": \"{}\", \"tokens\": {count}}}", json_escape(path)))
+            .collect();
+        println!(
+            "{{\"files\": [{}], \"total\": {total}, \"encoding\": \"{}\"}}",
+            files.join(", "),
+            json_escape(encoding)
+        );
+    } else {
+        for (path, count) in results {
+            println!("{count}\t{path}");
+        }
+        println!("{total}\ttotal");
+    }
+}
+
+fn json_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 fn read_stdin() -> Result<String, String> {
     let mut buffer = Vec::new();
     io::stdin()